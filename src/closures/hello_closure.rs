@@ -1,5 +1,6 @@
 use crate::z_owned_hello_t;
 use libc::c_void;
+use std::sync::RwLock;
 
 /// A closure is a structure that contains all the elements for stateful, memory-leak-free callbacks:
 ///
@@ -79,3 +80,164 @@ impl<F: Fn(&mut z_owned_hello_t)> From<F> for z_owned_closure_hello_t {
         }
     }
 }
+
+/// The `context`/`call`/`drop` triple of a `z_owned_closure_hello_t`.
+#[derive(Clone, Copy)]
+struct HelloClosureTriple {
+    context: *mut c_void,
+    call: Option<extern "C" fn(&mut z_owned_hello_t, *mut c_void)>,
+    drop: Option<extern "C" fn(*mut c_void)>,
+}
+impl HelloClosureTriple {
+    const EMPTY: Self = HelloClosureTriple {
+        context: std::ptr::null_mut(),
+        call: None,
+        drop: None,
+    };
+    fn call(&self, hello: &mut z_owned_hello_t) {
+        match self.call {
+            Some(call) => call(hello, self.context),
+            None => log::error!("Attempted to call an uninitialized closure!"),
+        }
+    }
+    fn drop(self) {
+        if let Some(drop) = self.drop {
+            drop(self.context)
+        }
+    }
+}
+impl From<z_owned_closure_hello_t> for HelloClosureTriple {
+    fn from(closure: z_owned_closure_hello_t) -> Self {
+        // `closure`'s fields are moved out below, so its `Drop` (which would otherwise run its
+        // `drop` callback immediately) must not run.
+        let closure = std::mem::ManuallyDrop::new(closure);
+        HelloClosureTriple {
+            context: closure.context,
+            call: closure.call,
+            drop: closure.drop,
+        }
+    }
+}
+// `HelloClosureTriple` is just a bag of raw pointers; the crate's `z_owned_closure_hello_t`
+// already treats those as safe to move and share across threads, so this does too.
+unsafe impl Send for HelloClosureTriple {}
+unsafe impl Sync for HelloClosureTriple {}
+
+/// An `RwLock`-guarded variant of `z_owned_closure_hello_t` that lets one thread call the
+/// closure while another replaces it with a new one.
+///
+/// `z_atomic_closure_hello_call` holds the read lock for the duration of the call, so any number
+/// of calls may run concurrently. `z_atomic_closure_hello_store` takes the write lock to install
+/// the new closure; doing so blocks until every `z_atomic_closure_hello_call` holding the read
+/// lock has returned, so the closure being replaced is only dropped once no call can still be
+/// using it, preserving the crate's "`drop` runs once, after every `call` has ended" guarantee.
+#[repr(C)]
+pub struct z_owned_atomic_closure_hello_t {
+    triple: RwLock<HelloClosureTriple>,
+}
+impl z_owned_atomic_closure_hello_t {
+    pub fn empty() -> Self {
+        z_owned_atomic_closure_hello_t {
+            triple: RwLock::new(HelloClosureTriple::EMPTY),
+        }
+    }
+}
+impl From<z_owned_closure_hello_t> for z_owned_atomic_closure_hello_t {
+    fn from(closure: z_owned_closure_hello_t) -> Self {
+        z_owned_atomic_closure_hello_t {
+            triple: RwLock::new(closure.into()),
+        }
+    }
+}
+impl Drop for z_owned_atomic_closure_hello_t {
+    fn drop(&mut self) {
+        let triple = self
+            .triple
+            .get_mut()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        std::mem::replace(triple, HelloClosureTriple::EMPTY).drop();
+    }
+}
+
+/// Constructs a gravestone value for an atomic hello closure, holding no callback.
+#[no_mangle]
+#[allow(clippy::missing_safety_doc)]
+pub unsafe extern "C" fn z_atomic_closure_hello_null() -> z_owned_atomic_closure_hello_t {
+    z_owned_atomic_closure_hello_t::empty()
+}
+
+/// Constructs an atomic hello closure wrapping `closure`, leaving `closure` empty as if
+/// `z_closure_hello_drop` had been called on it.
+#[no_mangle]
+#[allow(clippy::missing_safety_doc)]
+pub unsafe extern "C" fn z_atomic_closure_hello_new(
+    closure: &mut z_owned_closure_hello_t,
+) -> z_owned_atomic_closure_hello_t {
+    let mut taken = z_owned_closure_hello_t::empty();
+    std::mem::swap(&mut taken, closure);
+    taken.into()
+}
+
+/// Returns ``true`` if `closure` is valid, i.e. currently holds a callback rather than being a
+/// gravestone value.
+#[no_mangle]
+#[allow(clippy::missing_safety_doc)]
+pub unsafe extern "C" fn z_atomic_closure_hello_check(
+    closure: &z_owned_atomic_closure_hello_t,
+) -> bool {
+    closure
+        .triple
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .call
+        .is_some()
+}
+
+/// Drops `closure`, running its callback's `drop` and invalidating it for double-drop safety.
+#[no_mangle]
+#[allow(clippy::missing_safety_doc)]
+pub unsafe extern "C" fn z_atomic_closure_hello_drop(closure: &mut z_owned_atomic_closure_hello_t) {
+    let mut guard = closure
+        .triple
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    std::mem::replace(&mut *guard, HelloClosureTriple::EMPTY).drop();
+}
+
+/// Calls `closure`, holding its read lock for the duration of the call so that a concurrent
+/// `z_atomic_closure_hello_store` cannot drop the closure out from under it. Calling an
+/// uninitialized closure is a no-op.
+#[no_mangle]
+pub extern "C" fn z_atomic_closure_hello_call(
+    closure: &z_owned_atomic_closure_hello_t,
+    hello: &mut z_owned_hello_t,
+) {
+    let guard = closure
+        .triple
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    guard.call(hello);
+}
+
+/// Atomically replaces `closure`'s callback with `replacement`, leaving `replacement` empty as
+/// if `z_closure_hello_drop` had been called on it. This blocks until every in-progress
+/// `z_atomic_closure_hello_call` has returned, then drops the closure being replaced exactly
+/// once.
+#[no_mangle]
+pub extern "C" fn z_atomic_closure_hello_store(
+    closure: &z_owned_atomic_closure_hello_t,
+    replacement: &mut z_owned_closure_hello_t,
+) {
+    let mut taken = z_owned_closure_hello_t::empty();
+    std::mem::swap(&mut taken, replacement);
+    let new_triple: HelloClosureTriple = taken.into();
+
+    let old_triple = {
+        let mut guard = closure
+            .triple
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        std::mem::replace(&mut *guard, new_triple)
+    };
+    old_triple.drop();
+}
@@ -0,0 +1,178 @@
+use libc::{c_char, c_void};
+use std::ffi::CString;
+
+/// The severity of a log message forwarded to a `z_owned_closure_log_t`.
+///
+/// Members:
+///   Trace: used for the most verbose, per-operation diagnostics.
+///   Debug: used for detailed information useful to developers.
+///   Info: used for high-level information about normal operation.
+///   Warn: used for unexpected but recoverable conditions.
+///   Error: used for conditions that prevent an operation from succeeding.
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum zc_log_severity_t {
+    Trace = 0,
+    Debug = 1,
+    Info = 2,
+    Warn = 3,
+    Error = 4,
+}
+
+impl From<log::Level> for zc_log_severity_t {
+    fn from(level: log::Level) -> Self {
+        match level {
+            log::Level::Trace => zc_log_severity_t::Trace,
+            log::Level::Debug => zc_log_severity_t::Debug,
+            log::Level::Info => zc_log_severity_t::Info,
+            log::Level::Warn => zc_log_severity_t::Warn,
+            log::Level::Error => zc_log_severity_t::Error,
+        }
+    }
+}
+
+impl From<zc_log_severity_t> for log::LevelFilter {
+    fn from(severity: zc_log_severity_t) -> Self {
+        match severity {
+            zc_log_severity_t::Trace => log::LevelFilter::Trace,
+            zc_log_severity_t::Debug => log::LevelFilter::Debug,
+            zc_log_severity_t::Info => log::LevelFilter::Info,
+            zc_log_severity_t::Warn => log::LevelFilter::Warn,
+            zc_log_severity_t::Error => log::LevelFilter::Error,
+        }
+    }
+}
+
+/// A closure is a structure that contains all the elements for stateful, memory-leak-free callbacks:
+///
+/// Members:
+///   void *context: a pointer to an arbitrary state.
+///   void *call(zc_log_severity_t, const char *, void *context): the typical callback function. `context` will be passed as its last argument.
+///   void *drop(void*): allows the callback's state to be freed.
+///
+/// Closures are not guaranteed not to be called concurrently.
+///
+/// It is guaranteed that:
+///
+///   - `call` will never be called once `drop` has started.
+///   - `drop` will only be called **once**, and **after every** `call` has ended.
+///   - The two previous guarantees imply that `call` and `drop` are never called concurrently.
+#[repr(C)]
+pub struct z_owned_closure_log_t {
+    context: *mut c_void,
+    call: Option<extern "C" fn(zc_log_severity_t, *const c_char, *mut c_void)>,
+    drop: Option<extern "C" fn(*mut c_void)>,
+}
+
+impl z_owned_closure_log_t {
+    pub fn empty() -> Self {
+        z_owned_closure_log_t {
+            context: std::ptr::null_mut(),
+            call: None,
+            drop: None,
+        }
+    }
+}
+unsafe impl Send for z_owned_closure_log_t {}
+unsafe impl Sync for z_owned_closure_log_t {}
+impl Drop for z_owned_closure_log_t {
+    fn drop(&mut self) {
+        if let Some(drop) = self.drop {
+            drop(self.context)
+        }
+    }
+}
+/// Calls the closure. Calling an uninitialized closure is a no-op.
+#[no_mangle]
+pub extern "C" fn z_closure_log_call(
+    closure: &z_owned_closure_log_t,
+    severity: zc_log_severity_t,
+    msg: *const c_char,
+) {
+    match closure.call {
+        Some(call) => call(severity, msg, closure.context),
+        None => {
+            // Deliberately not `log::error!`: this closure backs `zc_init_log_with_callback`'s
+            // installed `log::Log`, so logging through the `log` crate here would re-enter
+            // `CLogger::log` and recurse forever on the very first record if that closure is
+            // left uninitialized.
+            eprintln!("Attempted to call an uninitialized closure!");
+        }
+    }
+}
+/// Drops the closure. Droping an uninitialized closure is a no-op.
+#[no_mangle]
+pub extern "C" fn z_closure_log_drop(closure: &mut z_owned_closure_log_t) {
+    let mut empty_closure = z_owned_closure_log_t::empty();
+    std::mem::swap(&mut empty_closure, closure);
+}
+impl<F: Fn(zc_log_severity_t, *const c_char)> From<F> for z_owned_closure_log_t {
+    fn from(f: F) -> Self {
+        let this = Box::into_raw(Box::new(f)) as _;
+        extern "C" fn call<F: Fn(zc_log_severity_t, *const c_char)>(
+            severity: zc_log_severity_t,
+            msg: *const c_char,
+            this: *mut c_void,
+        ) {
+            let this = unsafe { &*(this as *const F) };
+            this(severity, msg)
+        }
+        extern "C" fn drop<F>(this: *mut c_void) {
+            std::mem::drop(unsafe { Box::from_raw(this as *mut F) })
+        }
+        z_owned_closure_log_t {
+            context: this,
+            call: Some(call::<F>),
+            drop: Some(drop::<F>),
+        }
+    }
+}
+
+/// A logger which forwards every `log` record it receives to a `z_owned_closure_log_t`, except
+/// records whose message contains an interior NUL byte, which are discarded.
+struct CLogger {
+    closure: z_owned_closure_log_t,
+}
+impl log::Log for CLogger {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        true
+    }
+    fn log(&self, record: &log::Record) {
+        let msg = match CString::new(format!("{}", record.args())) {
+            Ok(msg) => msg,
+            Err(_) => return,
+        };
+        z_closure_log_call(&self.closure, record.level().into(), msg.as_ptr());
+    }
+    fn flush(&self) {}
+}
+unsafe impl Send for CLogger {}
+unsafe impl Sync for CLogger {}
+
+/// Installs a logger that forwards internal zenoh-c log messages to `callback`, allowing a C
+/// application to route them into its own logging pipeline instead of depending on a Rust `log`
+/// backend such as `env_logger`.
+///
+/// Parameters:
+///     level: The minimum severity of messages to forward to `callback`; less severe messages
+///            are discarded.
+///     callback: The closure to be called for each log message. It is NOT guaranteed to be called
+///               from the same thread that called `zc_init_log_with_callback`.
+///
+/// Like most `z_owned_X_t` consuming functions, `callback`'s value is consumed: it is left in an
+/// empty state as if `z_closure_log_drop` had been called on it.
+///
+/// Only the first logger installed in the process takes effect: if a `log` logger is already set,
+/// this installs nothing and `callback` is dropped.
+#[allow(clippy::missing_safety_doc)]
+#[no_mangle]
+pub extern "C" fn zc_init_log_with_callback(
+    level: zc_log_severity_t,
+    callback: &mut z_owned_closure_log_t,
+) {
+    let mut closure = z_owned_closure_log_t::empty();
+    std::mem::swap(&mut closure, callback);
+    if log::set_boxed_logger(Box::new(CLogger { closure })).is_ok() {
+        log::set_max_level(level.into());
+    }
+}
@@ -16,10 +16,15 @@ use async_std::task;
 use libc::{c_char, c_uint, c_ulong, size_t};
 use std::ffi::CString;
 use zenoh::scouting::Hello;
-use zenoh_protocol_core::{whatami::WhatAmIMatcher, WhatAmI};
+use zenoh_protocol_core::{locator::Locator, whatami::WhatAmIMatcher, WhatAmI};
 use zenoh_util::core::AsyncResolve;
 
-use crate::{z_closure_hello_call, z_id_t, z_owned_closure_hello_t, z_owned_config_t, Z_ROUTER};
+use std::sync::Arc;
+
+use crate::{
+    z_atomic_closure_hello_call, z_atomic_closure_hello_store, z_id_t, z_owned_atomic_closure_hello_t,
+    z_owned_closure_hello_t, z_owned_config_t, Z_ROUTER,
+};
 
 /// An owned array of owned, zenoh allocated, NULL terminated strings.
 ///
@@ -65,6 +70,8 @@ pub unsafe extern "C" fn z_str_array_check(strs: &z_owned_str_array_t) -> bool {
 ///   unsigned int whatami: The kind of zenoh entity.
 ///   z_owned_bytes_t pid: The peer id of the scouted entity (empty if absent).
 ///   z_owned_str_array_t locators: The locators of the scouted entity.
+///   int8_t convert_status: The outcome of the `Hello` to `z_owned_hello_t` conversion that
+///     produced this value; see `z_hello_convert_status`.
 ///
 /// Like all `z_owned_X_t`, an instance will be destroyed by any function which takes a mutable pointer to said instance, as this implies the instance's inners were moved.
 /// To make this fact more obvious when reading your code, consider using `z_move(val)` instead of `&val` as the argument.
@@ -76,9 +83,84 @@ pub struct z_owned_hello_t {
     pub whatami: c_uint,
     pub pid: z_id_t,
     pub locators: z_owned_str_array_t,
+    pub convert_status: i8,
+}
+
+/// Status codes carried by `z_owned_hello_t::convert_status` and returned by
+/// `z_hello_convert_status`, describing the outcome of the `Hello` to `z_owned_hello_t`
+/// conversion that produced a given `hello` value.
+const Z_HELLO_CONVERT_OK: i8 = 0;
+/// A locator's textual form contained an interior NUL byte, or reserving the locator array
+/// failed, while converting a scouted `Hello` into a `z_owned_hello_t`.
+const Z_HELLO_CONVERT_FAILED: i8 = -1;
+
+/// Reports the outcome of the `Hello` to `z_owned_hello_t` conversion that produced `hello`: `0`
+/// on success (including when the scouted entity reported no locators at all), and a negative
+/// value if a `CString` conversion or the locator array's reservation failed, in which case `hello` is a gravestone
+/// value rather than a partial one.
+#[no_mangle]
+#[allow(clippy::missing_safety_doc)]
+pub unsafe extern "C" fn z_hello_convert_status(hello: &z_owned_hello_t) -> i8 {
+    hello.convert_status
+}
+
+/// Owns a set of raw, `CString`-allocated locator pointers, freeing them on drop unless they
+/// have been handed off via `into_raw_parts`. Used to unwind cleanly, without leaking, when a
+/// locator array conversion fails partway through.
+struct RawLocators(Vec<*mut c_char>);
+impl RawLocators {
+    fn into_raw_parts(mut self) -> (*mut *mut c_char, size_t) {
+        let mut owned = std::mem::take(&mut self.0);
+        let val = owned.as_mut_ptr();
+        let len = owned.len();
+        std::mem::forget(owned);
+        (val, len as size_t)
+    }
+}
+impl Drop for RawLocators {
+    fn drop(&mut self) {
+        for ptr in self.0.drain(..) {
+            unsafe { std::mem::drop(CString::from_raw(ptr)) }
+        }
+    }
+}
+
+/// Converts `locators` into an owned, NULL terminated string array, or returns `None` if any
+/// locator's textual form contains an interior NUL byte or reserving the array's backing `Vec`
+/// fails. The allocation of each locator's string is not checked: a failure there is not turned
+/// into `None`. On failure, any locator already converted is freed rather than leaked.
+///
+/// The backing `Vec`'s capacity is reserved exactly once, up front, for `locators.len()`
+/// elements: `z_str_array_drop` reconstructs it via `Vec::from_raw_parts(ptr, len, len)`, so the
+/// success path must leave capacity equal to len, or that reconstruction deallocates with the
+/// wrong `Layout`.
+fn try_locators_into_str_array(locators: Vec<Locator>) -> Option<z_owned_str_array_t> {
+    let mut raw = RawLocators(Vec::new());
+    raw.0.try_reserve_exact(locators.len()).ok()?;
+    for l in locators {
+        let cstr = CString::new(l.to_string()).ok()?;
+        raw.0.push(cstr.into_raw());
+    }
+    let (val, len) = raw.into_raw_parts();
+    Some(z_owned_str_array_t { val, len })
 }
+
 impl From<Hello> for z_owned_hello_t {
     fn from(h: Hello) -> Self {
+        let locators = match h.locators {
+            Some(locators) => match try_locators_into_str_array(locators) {
+                Some(locators) => locators,
+                None => {
+                    let mut hello = unsafe { z_hello_null() };
+                    hello.convert_status = Z_HELLO_CONVERT_FAILED;
+                    return hello;
+                }
+            },
+            None => z_owned_str_array_t {
+                val: std::ptr::null_mut(),
+                len: 0,
+            },
+        };
         z_owned_hello_t {
             whatami: match h.whatami {
                 Some(whatami) => whatami as c_uint,
@@ -88,22 +170,8 @@ impl From<Hello> for z_owned_hello_t {
                 Some(id) => unsafe { std::mem::transmute(id) },
                 None => z_id_t { id: [0; 16] },
             },
-            locators: match h.locators {
-                Some(locators) => {
-                    let mut locators = locators
-                        .into_iter()
-                        .map(|l| CString::new(l.to_string()).unwrap().into_raw())
-                        .collect::<Vec<_>>();
-                    let val = locators.as_mut_ptr();
-                    let len = locators.len();
-                    std::mem::forget(locators);
-                    z_owned_str_array_t { val, len }
-                }
-                None => z_owned_str_array_t {
-                    val: std::ptr::null_mut(),
-                    len: 0,
-                },
-            },
+            locators,
+            convert_status: Z_HELLO_CONVERT_OK,
         }
     }
 }
@@ -127,6 +195,7 @@ pub unsafe extern "C" fn z_hello_null() -> z_owned_hello_t {
             val: std::ptr::null_mut(),
             len: 0,
         },
+        convert_status: Z_HELLO_CONVERT_OK,
     }
 }
 impl Drop for z_owned_hello_t {
@@ -141,38 +210,135 @@ pub unsafe extern "C" fn z_hello_check(hello: &z_owned_hello_t) -> bool {
     hello.whatami != 0 && z_str_array_check(&hello.locators)
 }
 
-/// Scout for routers and/or peers.
+/// The live state backing a `z_owned_scout_t`: the scouting task itself, plus the atomic closure
+/// it dispatches `Hello` messages through, shared with the task so that `z_scout_update_callback`
+/// can reconfigure it without tearing down the scout.
+struct ScoutState {
+    scout: zenoh::scouting::Scout<()>,
+    callback: Arc<z_owned_atomic_closure_hello_t>,
+}
+
+/// An owned zenoh scouting task, returned by `z_scout_start`.
+///
+/// Dropping it (via `z_scout_drop`) stops the scouting task and releases its callback, invoking
+/// the callback's `drop`.
+///
+/// Like all `z_owned_X_t`, an instance will be destroyed by any function which takes a mutable pointer to said instance, as this implies the instance's inners were moved.
+/// To make this fact more obvious when reading your code, consider using `z_move(val)` instead of `&val` as the argument.
+/// After a move, `val` will still exist, but will no longer be valid. The destructors are double-drop-safe, but other functions will still trust that your `val` is valid.
+///
+/// To check if `val` is still valid, you may use `z_X_check(&val)` (or `z_check(val)` if your compiler supports `_Generic`), which will return `true` if `val` is valid.
+#[repr(C)]
+pub struct z_owned_scout_t(Option<ScoutState>);
+
+/// Constructs a gravestone value for scout, useful to steal one from a callback
+#[no_mangle]
+#[allow(clippy::missing_safety_doc)]
+pub unsafe extern "C" fn z_scout_null() -> z_owned_scout_t {
+    z_owned_scout_t(None)
+}
+
+/// Returns ``true`` if `scout` is valid.
+#[no_mangle]
+#[allow(clippy::missing_safety_doc)]
+pub unsafe extern "C" fn z_scout_check(scout: &z_owned_scout_t) -> bool {
+    scout.0.is_some()
+}
+
+/// Stops `scout` from delivering further `Hello` messages and releases its callback (invoking
+/// its `drop`), invalidating `scout` for double-drop safety.
+#[no_mangle]
+#[allow(clippy::missing_safety_doc)]
+pub unsafe extern "C" fn z_scout_drop(scout: &mut z_owned_scout_t) {
+    std::mem::drop(scout.0.take());
+}
+impl Drop for z_owned_scout_t {
+    fn drop(&mut self) {
+        unsafe { z_scout_drop(self) };
+    }
+}
+
+/// Starts scouting for routers and/or peers, returning immediately without waiting for any
+/// `Hello` message to be received.
 ///
 /// Parameters:
 ///     what: A whatami bitmask of zenoh entities kind to scout for.
 ///     config: A set of properties to configure the scouting.
-///     timeout: The time (in milliseconds) that should be spent scouting.
+///     callback: The callback to invoke for each received `Hello` message.
 ///
 /// Returns:
-///     An array of `z_hello_t` messages.
+///     An owned scouting task. Scouting keeps running, calling `callback` for every `Hello`
+///     message received, until `z_scout_drop` is called on the returned handle. While scouting
+///     is running, `z_scout_update_callback` may be used to swap `callback` for another one
+///     without tearing the scout down.
 #[allow(clippy::missing_safety_doc)]
 #[no_mangle]
-pub unsafe extern "C" fn z_scout(
+pub unsafe extern "C" fn z_scout_start(
     what: c_uint,
     config: &mut z_owned_config_t,
     callback: &mut z_owned_closure_hello_t,
-    timeout: c_ulong,
-) {
+) -> z_owned_scout_t {
     let what = WhatAmIMatcher::try_from(what as u64).unwrap_or(WhatAmI::Router | WhatAmI::Peer);
     let config = config.as_mut().take().expect("invalid config");
     let mut closure = z_owned_closure_hello_t::empty();
     std::mem::swap(&mut closure, callback);
+    let callback = Arc::new(z_owned_atomic_closure_hello_t::from(closure));
+    let task_callback = callback.clone();
 
-    task::block_on(async move {
-        let scout = zenoh::scout(what, *config)
+    let scout = task::block_on(async move {
+        zenoh::scout(what, *config)
             .callback(move |h| {
                 let mut hello = h.into();
-                z_closure_hello_call(&closure, &mut hello)
+                z_atomic_closure_hello_call(&task_callback, &mut hello)
             })
             .res_async()
             .await
-            .unwrap();
-        async_std::task::sleep(std::time::Duration::from_millis(timeout as u64)).await;
-        std::mem::drop(scout);
+            .unwrap()
     });
+    z_owned_scout_t(Some(ScoutState { scout, callback }))
+}
+
+/// Atomically replaces the callback that a live `scout` dispatches `Hello` messages through with
+/// `replacement`, leaving `replacement` empty as if `z_closure_hello_drop` had been called on it.
+/// This is a no-op if `scout` is a gravestone value.
+///
+/// This blocks until every `Hello` delivery already in progress has returned, then drops the
+/// callback being replaced exactly once; it never stops or restarts the underlying scouting
+/// task, so no `Hello` message is missed while the swap happens.
+#[allow(clippy::missing_safety_doc)]
+#[no_mangle]
+pub unsafe extern "C" fn z_scout_update_callback(
+    scout: &z_owned_scout_t,
+    replacement: &mut z_owned_closure_hello_t,
+) {
+    if let Some(state) = &scout.0 {
+        z_atomic_closure_hello_store(&state.callback, replacement);
+    }
+}
+
+/// Scout for routers and/or peers for `timeout` milliseconds, blocking the calling thread for
+/// the whole duration.
+///
+/// Parameters:
+///     what: A whatami bitmask of zenoh entities kind to scout for.
+///     config: A set of properties to configure the scouting.
+///     timeout: The time (in milliseconds) that should be spent scouting.
+///
+/// Returns nothing: every `Hello` message received while scouting is passed to `callback`.
+///
+/// This is a thin wrapper around `z_scout_start` for callers that have no need to stop
+/// scouting early; use `z_scout_start`/`z_scout_drop` directly to do so.
+#[allow(clippy::missing_safety_doc)]
+#[no_mangle]
+pub unsafe extern "C" fn z_scout(
+    what: c_uint,
+    config: &mut z_owned_config_t,
+    callback: &mut z_owned_closure_hello_t,
+    timeout: c_ulong,
+) {
+    let mut scout = z_scout_start(what, config, callback);
+    task::block_on(async_std::task::sleep(std::time::Duration::from_millis(
+        timeout as u64,
+    )));
+    z_scout_drop(&mut scout);
 }